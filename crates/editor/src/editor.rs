@@ -408,6 +408,8 @@ struct RunnableTasks {
     column: u32,
     // Values of all named captures, including those starting with '_'
     extra_variables: HashMap<String, String>,
+    // Text of the run capture, provided as `ZED_RUNNABLE_SYMBOL`
+    runnable_symbol: String,
 }
 
 #[derive(Clone)]
@@ -511,6 +513,7 @@ pub struct Editor {
     expect_bounds_change: Option<Bounds<Pixels>>,
     tasks: HashMap<(BufferId, BufferRow), (usize, RunnableTasks)>,
     tasks_update_task: Option<Task<()>>,
+    task_context_cache: Option<tasks::TaskContextCache>,
 }
 
 #[derive(Clone)]
@@ -1723,6 +1726,7 @@ impl Editor {
                 }),
             ],
             tasks_update_task: None,
+            task_context_cache: None,
         };
         this.tasks_update_task = Some(this.refresh_runnables(cx));
         this._subscriptions.extend(project_subscriptions);
@@ -3990,6 +3994,10 @@ impl Editor {
                                     value.clone(),
                                 );
                             }
+                            captured_task_variables.insert(
+                                task::VariableName::RunnableSymbol,
+                                tasks.1.runnable_symbol.clone(),
+                            );
 
                             workspace
                                 .update(cx, |workspace, cx| {
@@ -7868,6 +7876,10 @@ impl Editor {
                 }
 
                 let point = runnable.run_range.start.to_point(&snapshot.buffer_snapshot);
+                let runnable_symbol = snapshot
+                    .buffer_snapshot
+                    .text_for_range(runnable.run_range.clone())
+                    .collect();
 
                 let row = snapshot
                     .buffer_snapshot
@@ -7884,6 +7896,7 @@ impl Editor {
                             templates: tasks,
                             column: point.column,
                             extra_variables: runnable.extra_captures,
+                            runnable_symbol,
                         },
                     ),
                 ))
@@ -1,11 +1,20 @@
+use std::{
+    cmp,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
 use crate::Editor;
 
 use anyhow::Context;
-use gpui::{Model, WindowContext};
+use collections::HashMap;
+use gpui::{Entity, Model, Task, View, ViewContext, WindowContext};
 use language::ContextProvider;
-use project::{BasicContextProvider, Location, Project};
+use project::{BasicContextProvider, Location, Project, WorktreeId};
 use task::{TaskContext, TaskVariables, VariableName};
-use text::Point;
+use text::{BufferId, Point, Selection};
 use util::ResultExt;
 use workspace::Workspace;
 
@@ -35,61 +44,181 @@ pub(crate) fn task_context_for_location(
     })
 }
 
+/// How long a computed [`TaskContext`] may be reused for, e.g. while a task keybinding is held or repeatedly pressed.
+/// Bounds staleness of the state that is not a part of [`TaskContextCacheKey`], like anything language context providers look up.
+const TASK_CONTEXT_CACHE_TTL: Duration = Duration::from_secs(1);
+
+/// The last [`TaskContext`] computed for the newest selection of the editor.
+pub(crate) struct TaskContextCache {
+    key: TaskContextCacheKey,
+    task_context: TaskContext,
+    // Drops the cache after [`TASK_CONTEXT_CACHE_TTL`].
+    _expire: Task<()>,
+}
+
+#[derive(PartialEq, Eq)]
+struct TaskContextCacheKey {
+    buffer_id: BufferId,
+    buffer_version: clock::Global,
+    // Changes when the buffer gets reparsed, e.g. after its language got detected.
+    parse_count: usize,
+    // Renames and "save as" change the buffer's file without bumping its version.
+    file: Option<(usize, Arc<Path>)>,
+    range: Range<text::Anchor>,
+    // Worktrees being added, removed or changing their visibility affect the worktree-derived variables.
+    worktrees: Vec<(WorktreeId, bool)>,
+    // With multiple worktrees, depends on the active entry.
+    cwd: Option<PathBuf>,
+}
+
+impl TaskContextCacheKey {
+    fn new(workspace: &Workspace, location: &Location, cx: &mut WindowContext<'_>) -> Self {
+        let cwd = workspace::tasks::task_cwd(workspace, cx)
+            .log_err()
+            .flatten();
+        let worktrees = workspace
+            .project()
+            .read(cx)
+            .worktrees()
+            .map(|worktree| {
+                let worktree = worktree.read(cx);
+                (worktree.id(), worktree.is_visible())
+            })
+            .collect();
+        let buffer = location.buffer.read(cx);
+        Self {
+            buffer_id: buffer.remote_id(),
+            buffer_version: buffer.version(),
+            parse_count: buffer.parse_count(),
+            file: buffer
+                .file()
+                .map(|file| (file.worktree_id(), file.path().clone())),
+            range: location.range.clone(),
+            worktrees,
+            cwd,
+        }
+    }
+}
+
 fn task_context_with_editor(
     workspace: &Workspace,
     editor: &mut Editor,
-    cx: &mut WindowContext<'_>,
+    cx: &mut ViewContext<'_, Editor>,
 ) -> Option<TaskContext> {
-    let (selection, buffer, editor_snapshot) = {
-        let mut selection = editor.selections.newest::<Point>(cx);
-        if editor.selections.line_mode {
-            selection.start = Point::new(selection.start.row, 0);
-            selection.end = Point::new(selection.end.row + 1, 0);
+    let selection = editor.selections.newest::<Point>(cx);
+    let (location, buffer_range) = location_for_selection(editor, selection, cx)?;
+    let cache_key = TaskContextCacheKey::new(workspace, &location, cx);
+    if let Some(cache) = &editor.task_context_cache {
+        if cache.key == cache_key {
+            return Some(cache.task_context.clone());
         }
-        let (buffer, _, _) = editor
-            .buffer()
-            .read(cx)
-            .point_to_buffer_offset(selection.start, cx)?;
-        let snapshot = editor.snapshot(cx);
-        Some((selection, buffer, snapshot))
-    }?;
-    let selection_range = selection.range();
-    let start = editor_snapshot
-        .display_snapshot
-        .buffer_snapshot
-        .anchor_after(selection_range.start)
-        .text_anchor;
-    let end = editor_snapshot
-        .display_snapshot
-        .buffer_snapshot
-        .anchor_after(selection_range.end)
-        .text_anchor;
-    let location = Location {
-        buffer,
-        range: start..end,
+    }
+
+    let task_context = task_context_for_editor_location(workspace, location, buffer_range, cx)?;
+    editor.task_context_cache = Some(TaskContextCache {
+        key: cache_key,
+        task_context: task_context.clone(),
+        _expire: cx.spawn(|editor, mut cx| async move {
+            cx.background_executor().timer(TASK_CONTEXT_CACHE_TTL).await;
+            editor
+                .update(&mut cx, |editor, _| editor.task_context_cache = None)
+                .ok();
+        }),
+    });
+    Some(task_context)
+}
+
+fn task_contexts_with_editor(
+    workspace: &Workspace,
+    editor: &mut Editor,
+    cx: &mut WindowContext<'_>,
+) -> Vec<TaskContext> {
+    editor
+        .selections
+        .all::<Point>(cx)
+        .into_iter()
+        // Selections that cannot be resolved into a buffer location are skipped, not failing the rest.
+        .filter_map(|selection| task_context_for_selection(workspace, editor, selection, cx))
+        .collect()
+}
+
+fn task_context_for_selection(
+    workspace: &Workspace,
+    editor: &mut Editor,
+    selection: Selection<Point>,
+    cx: &mut WindowContext<'_>,
+) -> Option<TaskContext> {
+    let (location, buffer_range) = location_for_selection(editor, selection, cx)?;
+    task_context_for_editor_location(workspace, location, buffer_range, cx)
+}
+
+/// Resolves the selection into a buffer location, along with its offset range in that buffer.
+fn location_for_selection(
+    editor: &Editor,
+    mut selection: Selection<Point>,
+    cx: &WindowContext<'_>,
+) -> Option<(Location, Range<usize>)> {
+    if editor.selections.line_mode {
+        // The last line may have no trailing newline, so the next row may not exist.
+        let max_point = editor.buffer().read(cx).snapshot(cx).max_point();
+        selection.start = Point::new(selection.start.row, 0);
+        selection.end = cmp::min(Point::new(selection.end.row + 1, 0), max_point);
+    }
+    // In a multibuffer, the selection may span excerpts of different buffers:
+    // resolve it through the excerpt it starts in, clipping the range to that excerpt.
+    let (buffer, buffer_range, _) = editor
+        .buffer()
+        .read(cx)
+        .range_to_buffer_ranges(selection.range(), cx)
+        .into_iter()
+        .next()?;
+    let range = {
+        let buffer = buffer.read(cx);
+        buffer.anchor_after(buffer_range.start)..buffer.anchor_after(buffer_range.end)
     };
+    Some((Location { buffer, range }, buffer_range))
+}
+
+fn task_context_for_editor_location(
+    workspace: &Workspace,
+    location: Location,
+    buffer_range: Range<usize>,
+    cx: &mut WindowContext<'_>,
+) -> Option<TaskContext> {
     let captured_variables = {
         let mut variables = TaskVariables::default();
-        for range in location
-            .buffer
-            .read(cx)
-            .snapshot()
-            .runnable_ranges(location.range.clone())
-        {
+        let buffer_snapshot = location.buffer.read(cx).snapshot();
+        let mut innermost_runnable = None::<(Range<usize>, Range<usize>)>;
+        for range in buffer_snapshot.runnable_ranges(location.range.clone()) {
             for (capture_name, value) in range.extra_captures {
                 variables.insert(VariableName::Custom(capture_name.into()), value);
             }
+            // Runnable queries may match items that do not enclose the selection.
+            let item_range = range.full_range;
+            let contains_selection =
+                item_range.start <= buffer_range.start && buffer_range.end <= item_range.end;
+            let is_innermost = innermost_runnable
+                .as_ref()
+                .map_or(true, |(innermost_range, _)| {
+                    item_range.len() < innermost_range.len()
+                });
+            if contains_selection && is_innermost {
+                innermost_runnable = Some((item_range, range.run_range));
+            }
+        }
+        if let Some((_, run_range)) = innermost_runnable {
+            variables.insert(
+                VariableName::RunnableSymbol,
+                buffer_snapshot.text_for_range(run_range).collect(),
+            );
         }
         variables
     };
-    task_context_for_location(captured_variables, workspace, location.clone(), cx)
+    task_context_for_location(captured_variables, workspace, location, cx)
 }
 
 pub fn task_context(workspace: &Workspace, cx: &mut WindowContext<'_>) -> TaskContext {
-    let Some(editor) = workspace
-        .active_item(cx)
-        .and_then(|item| item.act_as::<Editor>(cx))
-    else {
+    let Some(editor) = active_or_recent_editor(workspace, cx) else {
         return Default::default();
     };
     editor.update(cx, |editor, cx| {
@@ -97,6 +226,51 @@ pub fn task_context(workspace: &Workspace, cx: &mut WindowContext<'_>) -> TaskCo
     })
 }
 
+/// Same as [`task_context`], but produces a context for every selection of the active editor, in the selection order.
+/// Useful for multi-cursor runs, e.g. running a test under each caret.
+pub fn task_contexts(workspace: &Workspace, cx: &mut WindowContext<'_>) -> Vec<TaskContext> {
+    let Some(editor) = active_or_recent_editor(workspace, cx) else {
+        return Vec::new();
+    };
+    editor.update(cx, |editor, cx| {
+        task_contexts_with_editor(workspace, editor, cx)
+    })
+}
+
+/// Returns the active editor, or, if the active item is not an editor (e.g. a terminal),
+/// the most recently activated editor of the workspace.
+pub fn active_or_recent_editor(
+    workspace: &Workspace,
+    cx: &WindowContext<'_>,
+) -> Option<View<Editor>> {
+    if let Some(editor) = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))
+    {
+        return Some(editor);
+    }
+
+    let mut timestamps_by_entity_id = HashMap::default();
+    for pane in workspace.panes() {
+        for entry in pane.read(cx).activation_history() {
+            timestamps_by_entity_id.insert(entry.entity_id, entry.timestamp);
+        }
+    }
+    workspace
+        .items_of_type::<Editor>(cx)
+        .filter_map(|editor| {
+            let timestamp = timestamps_by_entity_id.get(&editor.entity_id()).copied()?;
+            Some((editor, timestamp))
+        })
+        .max_by_key(|(_, timestamp)| *timestamp)
+        .map(|(editor, _)| editor)
+}
+
+/// Task variables are combined in the following order, with later sources overwriting earlier ones on collision:
+/// tree-sitter captures of the runnables, [`BasicContextProvider`] variables, language-specific [`ContextProvider`] variables.
+/// Captures are stored as [`VariableName::Custom`] and so never collide with the built-in variables.
+/// They can still collide with the custom variables of the language provider (e.g. `RUST_PACKAGE`) and get overwritten by those,
+/// so runnables queries should not name their captures after provider variables.
 fn combine_task_variables(
     mut captured_variables: TaskVariables,
     location: Location,
@@ -3021,6 +3021,28 @@ impl BufferSnapshot {
                     .max_by_key(|(byte_range, _)| byte_range.len())
                     .map(|(range, _)| range)?
                     .clone();
+                // A pattern may match several sibling nodes (e.g. a Rust test function preceded by its attributes),
+                // while its tag only captures the first of them, so extend the range over all top-level nodes of the match.
+                let tag_node = mat
+                    .captures
+                    .iter()
+                    .find(|capture| {
+                        test_configs.runnable_tags.contains_key(&capture.index)
+                            && capture.node.byte_range() == maximum_range
+                    })?
+                    .node;
+                let mut full_range = maximum_range.clone();
+                for capture in mat.captures {
+                    let mut node = capture.node;
+                    while let Some(parent) = node.parent() {
+                        if Some(parent) == tag_node.parent() {
+                            break;
+                        }
+                        node = parent;
+                    }
+                    full_range.start = full_range.start.min(node.start_byte());
+                    full_range.end = full_range.end.max(node.end_byte());
+                }
                 tags.sort_by_key(|(range, _)| range == &maximum_range);
                 let split_point = tags.partition_point(|(range, _)| range != &maximum_range);
                 let (extra_captures, tags) = tags.split_at(split_point);
@@ -3040,6 +3062,7 @@ impl BufferSnapshot {
                         .iter()
                         .find(|capture| capture.index == test_configs.run_capture_ix)
                         .map(|mat| mat.node.byte_range())?,
+                    full_range,
                     runnable: Runnable {
                         tags: tags.into_iter().cloned().map(|(_, tag)| tag).collect(),
                         language: mat.language,
@@ -11,6 +11,8 @@ use text::BufferId;
 pub struct RunnableRange {
     pub buffer_id: BufferId,
     pub run_range: Range<usize>,
+    /// The whole extent of the runnable match, e.g. a test function along with its attributes.
+    pub full_range: Range<usize>,
     pub runnable: Runnable,
     pub extra_captures: HashMap<String, String>,
 }
@@ -3214,6 +3214,12 @@ impl MultiBufferSnapshot {
                             excerpt_offset + runnable.run_range.start - excerpt_buffer_start;
                         runnable.run_range.end =
                             excerpt_offset + runnable.run_range.end - excerpt_buffer_start;
+                        // The run indicator is within the excerpt, but the whole runnable may start before it.
+                        let full_range_start = runnable.full_range.start.max(excerpt_buffer_start);
+                        runnable.full_range.start =
+                            excerpt_offset + full_range_start - excerpt_buffer_start;
+                        runnable.full_range.end =
+                            excerpt_offset + runnable.full_range.end - excerpt_buffer_start;
                         Some(runnable)
                     })
                     .skip_while(move |runnable| runnable.run_range.end < range.start)
@@ -530,7 +530,7 @@ impl ContextProvider for BasicContextProvider {
         let current_file = buffer
             .file()
             .and_then(|file| file.as_local())
-            .map(|file| file.abs_path(cx).to_string_lossy().to_string());
+            .map(|file| file.abs_path(cx));
         let Point { row, column } = location.range.start.to_point(&buffer_snapshot);
         let row = row + 1;
         let column = column + 1;
@@ -549,8 +549,11 @@ impl ContextProvider for BasicContextProvider {
         if !selected_text.trim().is_empty() {
             task_variables.insert(VariableName::SelectedText, selected_text);
         }
-        if let Some(path) = current_file {
-            task_variables.insert(VariableName::File, path);
+        if let Some(path) = &current_file {
+            task_variables.insert(VariableName::File, path.to_string_lossy().to_string());
+            if let Some(dirname) = path.parent() {
+                task_variables.insert(VariableName::Dirname, dirname.to_string_lossy().to_string());
+            }
         }
 
         let worktree_abs_path = buffer
@@ -560,8 +563,25 @@ impl ContextProvider for BasicContextProvider {
                 self.project
                     .read(cx)
                     .worktree_for_id(worktree_id, cx)
+                    // Files opened outside of the project live in invisible single-file worktrees,
+                    // rooted at the file itself, so there is no meaningful worktree root for them.
+                    .filter(|worktree| worktree.read(cx).is_visible())
                     .map(|worktree| worktree.read(cx).abs_path())
             });
+        let relative_file = buffer
+            .file()
+            .filter(|_| worktree_abs_path.is_some())
+            .map(|file| file.path().to_string_lossy().to_string())
+            .filter(|path| !path.is_empty())
+            // Outside of every worktree, fall back to the absolute path.
+            .or_else(|| {
+                current_file
+                    .as_ref()
+                    .map(|path| path.to_string_lossy().to_string())
+            });
+        if let Some(relative_file) = relative_file {
+            task_variables.insert(VariableName::RelativeFile, relative_file);
+        }
         if let Some(worktree_path) = worktree_abs_path {
             task_variables.insert(
                 VariableName::WorktreeRoot,
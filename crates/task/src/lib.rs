@@ -124,20 +124,30 @@ impl ResolvedTask {
 pub enum VariableName {
     /// An absolute path of the currently opened file.
     File,
+    /// A path of the currently opened file, relative to the root of the worktree that contains it.
+    /// Same as [`VariableName::File`] for files outside of every worktree.
+    RelativeFile,
+    /// An absolute path of the directory that contains the currently opened file.
+    Dirname,
     /// An absolute path of the currently opened worktree, that contains the file.
     WorktreeRoot,
     /// A symbol text, that contains latest cursor/selection position.
     Symbol,
-    /// A row with the latest cursor/selection position.
+    /// A row with the latest cursor/selection position, 1-based.
+    /// For non-empty selections, this is the row of the selection start, not of its head.
     Row,
-    /// A column with the latest cursor/selection position.
+    /// A column with the latest cursor/selection position, 1-based.
+    /// For non-empty selections, this is the column of the selection start, not of its head.
     Column,
-    /// Text from the latest selection.
+    /// Text from the latest selection, verbatim.
+    /// Not set for empty or whitespace-only selections, so that tasks requiring a selection are not resolved without one.
     SelectedText,
     /// The symbol selected by the symbol tagging system, specifically the @run capture in a runnables.scm
+    /// of the innermost runnable that contains the latest cursor/selection position.
     RunnableSymbol,
     /// Custom variable, provided by the plugin or other external source.
-    /// Will be printed with `ZED_` prefix to avoid potential conflicts with other variables.
+    /// Will be printed with `ZED_CUSTOM_` prefix to avoid potential conflicts with other variables, including the built-in ones:
+    /// e.g. a custom `ROW` variable is `ZED_CUSTOM_ROW` and does not shadow [`VariableName::Row`] (`ZED_ROW`).
     Custom(Cow<'static, str>),
 }
 
@@ -160,6 +170,8 @@ impl std::fmt::Display for VariableName {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Self::File => write!(f, "{ZED_VARIABLE_NAME_PREFIX}FILE"),
+            Self::RelativeFile => write!(f, "{ZED_VARIABLE_NAME_PREFIX}RELATIVE_FILE"),
+            Self::Dirname => write!(f, "{ZED_VARIABLE_NAME_PREFIX}DIRNAME"),
             Self::WorktreeRoot => write!(f, "{ZED_VARIABLE_NAME_PREFIX}WORKTREE_ROOT"),
             Self::Symbol => write!(f, "{ZED_VARIABLE_NAME_PREFIX}SYMBOL"),
             Self::Row => write!(f, "{ZED_VARIABLE_NAME_PREFIX}ROW"),
@@ -668,6 +668,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_custom_variables_do_not_shadow_built_in_ones() {
+        let custom_row = VariableName::Custom(Cow::Borrowed("ROW"));
+        assert_eq!(custom_row.to_string(), "ZED_CUSTOM_ROW");
+        assert_ne!(custom_row.to_string(), VariableName::Row.to_string());
+
+        let cx = TaskContext {
+            cwd: None,
+            task_variables: TaskVariables::from_iter([
+                (VariableName::Row, "5".to_string()),
+                (custom_row.clone(), "captured_row".to_string()),
+            ]),
+        };
+        assert_eq!(cx.task_variables.get(&VariableName::Row), Some("5"));
+        assert_eq!(cx.task_variables.get(&custom_row), Some("captured_row"));
+
+        let task = TaskTemplate {
+            label: "My task".into(),
+            command: "echo".into(),
+            args: vec![
+                VariableName::Row.template_value(),
+                custom_row.template_value(),
+            ],
+            ..Default::default()
+        };
+        let resolved_task = task.resolve_task(TEST_ID_BASE, &cx).unwrap();
+        assert_substituted_variables(&resolved_task, vec![VariableName::Row, custom_row]);
+        let resolved = resolved_task.resolved.unwrap();
+        assert_eq!(
+            resolved.command_label, "echo 5 captured_row",
+            "Both built-in and custom variables should be substituted independently"
+        );
+    }
+
     #[track_caller]
     fn assert_substituted_variables(resolved_task: &ResolvedTask, mut expected: Vec<VariableName>) {
         let mut resolved_variables = resolved_task
@@ -134,6 +134,11 @@ impl TryFrom<VsCodeTaskFile> for TaskTemplates {
                 VariableName::WorktreeRoot.to_string(),
             ),
             ("file".to_owned(), VariableName::File.to_string()),
+            (
+                "relativeFile".to_owned(),
+                VariableName::RelativeFile.to_string(),
+            ),
+            ("fileDirname".to_owned(), VariableName::Dirname.to_string()),
             ("lineNumber".to_owned(), VariableName::Row.to_string()),
             (
                 "selectedText".to_owned(),
@@ -388,4 +393,33 @@ mod tests {
         let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
         assert_eq!(tasks.0, expected);
     }
+
+    #[test]
+    fn can_substitute_file_variables() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "Lint File",
+                        "type": "shell",
+                        "command": "eslint",
+                        "args": ["${relativeFile}"],
+                        "options": { "cwd": "${fileDirname}" }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0,
+            vec![TaskTemplate {
+                label: "Lint File".to_string(),
+                command: "eslint".to_string(),
+                args: vec!["${ZED_RELATIVE_FILE}".to_string()],
+                cwd: Some("${ZED_DIRNAME}".to_string()),
+                ..Default::default()
+            }]
+        );
+    }
 }
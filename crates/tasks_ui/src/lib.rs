@@ -1,11 +1,12 @@
 use std::sync::Arc;
 
 use ::settings::Settings;
-use editor::{tasks::task_context, Editor};
+use editor::tasks::{active_or_recent_editor, task_context};
 use gpui::{AppContext, ViewContext, WindowContext};
 use language::Language;
 use modal::TasksModal;
 use project::WorktreeId;
+use workspace::item::ItemHandle;
 use workspace::tasks::schedule_task;
 use workspace::{tasks::schedule_resolved_task, Workspace};
 
@@ -127,38 +128,44 @@ fn active_item_selection_properties(
     workspace: &Workspace,
     cx: &mut WindowContext,
 ) -> (Option<WorktreeId>, Option<Arc<Language>>) {
-    let active_item = workspace.active_item(cx);
-    let worktree_id = active_item
-        .as_ref()
-        .and_then(|item| item.project_path(cx))
-        .map(|path| path.worktree_id);
-    let language = active_item
-        .and_then(|active_item| active_item.act_as::<Editor>(cx))
-        .and_then(|editor| {
-            editor.update(cx, |editor, cx| {
-                let selection = editor.selections.newest::<usize>(cx);
-                let (buffer, buffer_position, _) = editor
-                    .buffer()
-                    .read(cx)
-                    .point_to_buffer_offset(selection.start, cx)?;
-                buffer.read(cx).language_at(buffer_position)
-            })
-        });
+    // Same as for the task context, fall back to the most recent editor when e.g. a terminal is active.
+    let Some(editor) = active_or_recent_editor(workspace, cx) else {
+        return (None, None);
+    };
+    let worktree_id = editor.project_path(cx).map(|path| path.worktree_id);
+    let language = editor.update(cx, |editor, cx| {
+        let selection = editor.selections.newest::<usize>(cx);
+        let (buffer, buffer_position, _) = editor
+            .buffer()
+            .read(cx)
+            .point_to_buffer_offset(selection.start, cx)?;
+        buffer.read(cx).language_at(buffer_position)
+    });
     (worktree_id, language)
 }
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::{
+        path::PathBuf,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
 
-    use editor::Editor;
-    use gpui::{Entity, TestAppContext};
-    use language::{Language, LanguageConfig};
-    use project::{BasicContextProvider, FakeFs, Project};
+    use editor::{
+        actions::ToggleCodeActions, display_map::DisplayRow, tasks::task_contexts, Editor,
+        ExcerptRange, MultiBuffer,
+    };
+    use gpui::{AppContext, Context, Entity, TestAppContext, View, VisualTestContext};
+    use language::{Capability, ContextProvider, Language, LanguageConfig, Location};
+    use project::{BasicContextProvider, ContextProviderWithTasks, FakeFs, Project, WorktreeId};
     use serde_json::json;
-    use task::{TaskContext, TaskVariables, VariableName};
+    use task::{TaskContext, TaskTemplate, TaskTemplates, TaskVariables, VariableName};
     use ui::VisualContext;
-    use workspace::{AppState, Workspace};
+    use workspace::{item::test::TestItem, AppState, Workspace};
 
     use crate::task_context;
 
@@ -260,6 +267,8 @@ mod tests {
                     cwd: Some("/dir".into()),
                     task_variables: TaskVariables::from_iter([
                         (VariableName::File, "/dir/rust/b.rs".into()),
+                        (VariableName::RelativeFile, "rust/b.rs".into()),
+                        (VariableName::Dirname, "/dir/rust".into()),
                         (VariableName::WorktreeRoot, "/dir".into()),
                         (VariableName::Row, "1".into()),
                         (VariableName::Column, "1".into()),
@@ -276,6 +285,8 @@ mod tests {
                     cwd: Some("/dir".into()),
                     task_variables: TaskVariables::from_iter([
                         (VariableName::File, "/dir/rust/b.rs".into()),
+                        (VariableName::RelativeFile, "rust/b.rs".into()),
+                        (VariableName::Dirname, "/dir/rust".into()),
                         (VariableName::WorktreeRoot, "/dir".into()),
                         (VariableName::Row, "1".into()),
                         (VariableName::Column, "15".into()),
@@ -293,6 +304,8 @@ mod tests {
                     cwd: Some("/dir".into()),
                     task_variables: TaskVariables::from_iter([
                         (VariableName::File, "/dir/a.ts".into()),
+                        (VariableName::RelativeFile, "a.ts".into()),
+                        (VariableName::Dirname, "/dir".into()),
                         (VariableName::WorktreeRoot, "/dir".into()),
                         (VariableName::Row, "1".into()),
                         (VariableName::Column, "1".into()),
@@ -303,6 +316,616 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_row_and_column_variables(cx: &mut TestAppContext) {
+        let (workspace, editor, cx) =
+            editor_for_text("fn main() {\n    let x = 1;\n}\n", None, cx).await;
+        workspace.update(cx, |this, cx| {
+            // Place the caret right before `x` on the second line.
+            editor.update(cx, |this, cx| {
+                this.change_selections(None, cx, |selections| selections.select_ranges([20..20]))
+            });
+            let task_variables = task_context(this, cx).task_variables;
+            assert_eq!(task_variables.get(&VariableName::Row), Some("2"));
+            assert_eq!(task_variables.get(&VariableName::Column), Some("9"));
+
+            // For non-empty selections, the position is the selection start, regardless of the selection direction.
+            for selection in [16..22, 22..16] {
+                editor.update(cx, |this, cx| {
+                    this.change_selections(None, cx, |selections| {
+                        selections.select_ranges([selection.clone()])
+                    })
+                });
+                let task_variables = task_context(this, cx).task_variables;
+                assert_eq!(task_variables.get(&VariableName::Row), Some("2"));
+                assert_eq!(
+                    task_variables.get(&VariableName::Column),
+                    Some("5"),
+                    "Unexpected column for the selection {selection:?}"
+                );
+            }
+        });
+    }
+
+    #[gpui::test]
+    async fn test_task_context_per_selection(cx: &mut TestAppContext) {
+        let (workspace, editor, cx) =
+            editor_for_text("fn first() {}\nfn second() {}\n", None, cx).await;
+        workspace.update(cx, |this, cx| {
+            editor.update(cx, |this, cx| {
+                this.change_selections(None, cx, |selections| {
+                    selections.select_ranges([3..3, 17..17])
+                })
+            });
+            let rows_and_columns = task_contexts(this, cx)
+                .into_iter()
+                .map(|task_context| {
+                    let variables = task_context.task_variables;
+                    (
+                        variables.get(&VariableName::Row).map(ToOwned::to_owned),
+                        variables.get(&VariableName::Column).map(ToOwned::to_owned),
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                rows_and_columns,
+                vec![
+                    (Some("1".to_owned()), Some("4".to_owned())),
+                    (Some("2".to_owned()), Some("4".to_owned())),
+                ]
+            );
+            assert_eq!(
+                task_context(this, cx)
+                    .task_variables
+                    .get(&VariableName::Row),
+                Some("2"),
+                "Single task context should be produced for the newest selection"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_task_context_falls_back_to_recent_editor(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.rs": "fn a() {}",
+                "b.rs": "fn b() {}",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let editor_a = open_editor(&workspace, worktree_id, "a.rs", cx).await;
+        let editor_b = open_editor(&workspace, worktree_id, "b.rs", cx).await;
+        // Stands in for a terminal or any other non-editor item.
+        let non_editor_item = cx.new_view(TestItem::new);
+        workspace.update(cx, |this, cx| {
+            this.add_item_to_center(Box::new(editor_b.clone()), cx);
+            this.add_item_to_center(Box::new(editor_a.clone()), cx);
+            this.add_item_to_center(Box::new(non_editor_item.clone()), cx);
+            assert_eq!(
+                this.active_item(cx).unwrap().item_id(),
+                non_editor_item.entity_id()
+            );
+            assert_eq!(
+                task_context(this, cx),
+                TaskContext {
+                    cwd: Some("/dir".into()),
+                    task_variables: TaskVariables::from_iter([
+                        (VariableName::File, "/dir/a.rs".into()),
+                        (VariableName::RelativeFile, "a.rs".into()),
+                        (VariableName::Dirname, "/dir".into()),
+                        (VariableName::WorktreeRoot, "/dir".into()),
+                        (VariableName::Row, "1".into()),
+                        (VariableName::Column, "1".into()),
+                    ])
+                },
+                "With a non-editor item active, the most recently activated editor should be used"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_task_context_without_editors(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ "a.rs": "fn a() {}" })).await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let non_editor_item = cx.new_view(TestItem::new);
+        workspace.update(cx, |this, cx| {
+            this.add_item_to_center(Box::new(non_editor_item), cx);
+            assert_eq!(task_context(this, cx), TaskContext::default());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_task_context_in_multibuffer(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.rs": "fn a() {}",
+                "b.rs": "fn b() {}",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let mut buffers = Vec::new();
+        for path in ["a.rs", "b.rs"] {
+            let buffer = project
+                .update(cx, |project, cx| {
+                    project.open_buffer((worktree_id, path), cx)
+                })
+                .await
+                .unwrap();
+            buffers.push(buffer);
+        }
+        let multibuffer = cx.new_model(|cx| {
+            let mut multibuffer = MultiBuffer::new(0, Capability::ReadWrite);
+            for buffer in buffers {
+                multibuffer.push_excerpts(
+                    buffer,
+                    [ExcerptRange {
+                        context: 0..9,
+                        primary: None,
+                    }],
+                    cx,
+                );
+            }
+            multibuffer
+        });
+        let editor =
+            cx.new_view(|cx| Editor::for_multibuffer(multibuffer, Some(project.clone()), cx));
+        workspace.update(cx, |this, cx| {
+            this.add_item_to_center(Box::new(editor.clone()), cx);
+            // The caret on `b` in the second excerpt.
+            editor.update(cx, |this, cx| {
+                this.change_selections(None, cx, |selections| selections.select_ranges([13..13]))
+            });
+            let task_variables = task_context(this, cx).task_variables;
+            assert_eq!(task_variables.get(&VariableName::File), Some("/dir/b.rs"));
+            assert_eq!(task_variables.get(&VariableName::Row), Some("1"));
+            assert_eq!(task_variables.get(&VariableName::Column), Some("4"));
+
+            // A selection spanning both excerpts gets clipped to the one it starts in.
+            editor.update(cx, |this, cx| {
+                this.change_selections(None, cx, |selections| selections.select_ranges([3..13]))
+            });
+            let task_variables = task_context(this, cx).task_variables;
+            assert_eq!(task_variables.get(&VariableName::File), Some("/dir/a.rs"));
+            assert_eq!(
+                task_variables.get(&VariableName::SelectedText),
+                Some("a() {}")
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_runnable_symbol_variable(cx: &mut TestAppContext) {
+        let text = r#"#[test]
+fn outer() {
+    #[test]
+    fn inner() {
+        let x = 1;
+    }
+    let y = 2;
+}
+fn not_a_test() {
+    let z = 3;
+}
+"#;
+        let language = rust_language()
+            .with_runnable_query(RUST_TEST_RUNNABLES_QUERY)
+            .unwrap();
+        let (workspace, editor, cx) = editor_for_text(text, Some(Arc::new(language)), cx).await;
+        workspace.update(cx, |this, cx| {
+            for (caret_before, expected_symbol) in [
+                ("x = 1", Some("inner")),
+                ("#[test]\n    fn inner", Some("inner")),
+                ("y = 2", Some("outer")),
+                ("#[test]\nfn outer", Some("outer")),
+                ("z = 3", None),
+            ] {
+                let offset = text.find(caret_before).unwrap();
+                editor.update(cx, |this, cx| {
+                    this.change_selections(None, cx, |selections| {
+                        selections.select_ranges([offset..offset])
+                    })
+                });
+                assert_eq!(
+                    task_context(this, cx)
+                        .task_variables
+                        .get(&VariableName::RunnableSymbol),
+                    expected_symbol,
+                    "Unexpected runnable symbol for the caret before {caret_before:?}"
+                );
+            }
+        });
+    }
+
+    #[gpui::test]
+    async fn test_runnable_symbol_not_captured_from_item_name(cx: &mut TestAppContext) {
+        let text = r#"fn main() {
+    test("adds numbers", || {
+        let x = 1;
+    });
+    let y = 2;
+}
+"#;
+        // Similar to the Pest runnables, where the test description is the run capture.
+        let language = rust_language()
+            .with_runnable_query(
+                r#"(call_expression
+                    function: (identifier) @_name
+                    (#eq? @_name "test")
+                    arguments: (arguments . (string_literal) @run)
+                ) @closure-test"#,
+            )
+            .unwrap();
+        let (workspace, editor, cx) = editor_for_text(text, Some(Arc::new(language)), cx).await;
+        workspace.update(cx, |this, cx| {
+            for (caret_before, expected_symbol) in
+                [("x = 1", Some("\"adds numbers\"")), ("y = 2", None)]
+            {
+                let offset = text.find(caret_before).unwrap();
+                editor.update(cx, |this, cx| {
+                    this.change_selections(None, cx, |selections| {
+                        selections.select_ranges([offset..offset])
+                    })
+                });
+                assert_eq!(
+                    task_context(this, cx)
+                        .task_variables
+                        .get(&VariableName::RunnableSymbol),
+                    expected_symbol,
+                    "Unexpected runnable symbol for the caret before {caret_before:?}"
+                );
+            }
+        });
+    }
+
+    #[gpui::test]
+    async fn test_captures_do_not_shadow_built_in_variables(cx: &mut TestAppContext) {
+        let text = "#[test]\nfn a_test() {\n    let x = 1;\n}\n";
+        // The attribute name is captured as `row`, same as the built-in `ZED_ROW` variable.
+        let language = rust_language()
+            .with_runnable_query(
+                r#"(
+                    (attribute_item (attribute (identifier) @row)
+                        (#eq? @row "test"))
+                    .
+                    (function_item name: (_) @run)
+                ) @rust-test"#,
+            )
+            .unwrap();
+        let (workspace, editor, cx) = editor_for_text(text, Some(Arc::new(language)), cx).await;
+        workspace.update(cx, |this, cx| {
+            let offset = text.find("x = 1").unwrap();
+            editor.update(cx, |this, cx| {
+                this.change_selections(None, cx, |selections| {
+                    selections.select_ranges([offset..offset])
+                })
+            });
+            let task_context = task_context(this, cx);
+            assert_eq!(
+                task_context.task_variables.get(&VariableName::Row),
+                Some("3")
+            );
+            assert_eq!(
+                task_context
+                    .task_variables
+                    .get(&VariableName::Custom("row".into())),
+                Some("test")
+            );
+
+            let resolved_task = TaskTemplate {
+                label: "rows".to_string(),
+                command: "echo".to_string(),
+                args: vec!["$ZED_ROW".to_string(), "$ZED_CUSTOM_row".to_string()],
+                ..TaskTemplate::default()
+            }
+            .resolve_task("test", &task_context)
+            .unwrap();
+            assert_eq!(
+                resolved_task.resolved.unwrap().args,
+                vec!["3".to_string(), "test".to_string()]
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_runnable_symbol_from_gutter(cx: &mut TestAppContext) {
+        let text = "#[test]\nfn first() {}\n#[test]\nfn second() {}\n";
+        let language = rust_language()
+            .with_runnable_query(RUST_TEST_RUNNABLES_QUERY)
+            .unwrap()
+            .with_context_provider(Some(Arc::new(ContextProviderWithTasks::new(
+                TaskTemplates(vec![TaskTemplate {
+                    label: "test $ZED_RUNNABLE_SYMBOL".to_string(),
+                    command: "cargo".to_string(),
+                    args: vec!["test".to_string(), "$ZED_RUNNABLE_SYMBOL".to_string()],
+                    tags: vec!["rust-test".to_string()],
+                    ..TaskTemplate::default()
+                }]),
+            ))));
+        let (workspace, editor, cx) = editor_for_text(text, Some(Arc::new(language)), cx).await;
+        cx.focus_view(&editor);
+        // The caret stays in the first test, the run button of the second one is clicked.
+        editor.update(cx, |editor, cx| {
+            editor.toggle_code_actions(
+                &ToggleCodeActions {
+                    deployed_from_indicator: Some(DisplayRow(3)),
+                },
+                cx,
+            )
+        });
+        cx.run_until_parked();
+
+        let (_, scheduled_task) = workspace
+            .update(cx, |this, cx| {
+                this.project()
+                    .read(cx)
+                    .task_inventory()
+                    .read(cx)
+                    .last_scheduled_task()
+            })
+            .expect("The only gutter task should be spawned straight away");
+        assert_eq!(scheduled_task.resolved_label, "test second");
+        assert_eq!(
+            scheduled_task.resolved.unwrap().args,
+            vec!["test".to_string(), "second".to_string()]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_line_mode_selection_on_last_line(cx: &mut TestAppContext) {
+        let (workspace, editor, cx) = editor_for_text("fn main() {}", None, cx).await;
+        workspace.update(cx, |this, cx| {
+            editor.update(cx, |this, cx| {
+                this.selections.line_mode = true;
+                this.change_selections(None, cx, |selections| selections.select_ranges([3..3]))
+            });
+            let task_variables = task_context(this, cx).task_variables;
+            assert_eq!(
+                task_variables.get(&VariableName::SelectedText),
+                Some("fn main() {}"),
+                "Line mode selection should cover the whole last line without a trailing newline"
+            );
+            assert_eq!(task_variables.get(&VariableName::Row), Some("1"));
+            assert_eq!(task_variables.get(&VariableName::Column), Some("1"));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_selected_text_variable(cx: &mut TestAppContext) {
+        let text = "fn main() {\n    let x = 1;\n    let y = 2;\n}\n";
+        let (workspace, editor, cx) = editor_for_text(text, None, cx).await;
+        workspace.update(cx, |this, cx| {
+            assert_eq!(
+                task_context(this, cx)
+                    .task_variables
+                    .get(&VariableName::SelectedText),
+                None,
+                "No selected text should be set for an empty selection"
+            );
+
+            for selected_text in ["x = 1", "let x = 1;\n    let y"] {
+                let start = text.find(selected_text).unwrap();
+                let end = start + selected_text.len();
+                editor.update(cx, |this, cx| {
+                    this.change_selections(None, cx, |selections| {
+                        selections.select_ranges([start..end])
+                    })
+                });
+                assert_eq!(
+                    task_context(this, cx)
+                        .task_variables
+                        .get(&VariableName::SelectedText),
+                    Some(selected_text),
+                );
+            }
+        });
+    }
+
+    #[derive(Default)]
+    struct CountingContextProvider {
+        build_context_calls: AtomicUsize,
+    }
+
+    impl ContextProvider for CountingContextProvider {
+        fn build_context(
+            &self,
+            _: &TaskVariables,
+            _: &Location,
+            _: &mut AppContext,
+        ) -> gpui::Result<TaskVariables> {
+            self.build_context_calls.fetch_add(1, Ordering::SeqCst);
+            Ok(TaskVariables::default())
+        }
+    }
+
+    #[gpui::test]
+    async fn test_task_context_is_cached_until_changes(cx: &mut TestAppContext) {
+        let text = "fn main() { let abc = 1; }";
+        let context_provider = Arc::new(CountingContextProvider::default());
+        let language = rust_language().with_context_provider(Some(context_provider.clone()));
+        let (workspace, editor, cx) = editor_for_text(text, Some(Arc::new(language)), cx).await;
+        let build_context_calls = || context_provider.build_context_calls.load(Ordering::SeqCst);
+        let start = text.find("abc").unwrap();
+        workspace.update(cx, |this, cx| {
+            editor.update(cx, |this, cx| {
+                this.change_selections(None, cx, |selections| {
+                    selections.select_ranges([start..start + 3])
+                })
+            });
+            let first_context = task_context(this, cx);
+            assert_eq!(
+                first_context
+                    .task_variables
+                    .get(&VariableName::SelectedText),
+                Some("abc"),
+            );
+            assert_eq!(build_context_calls(), 1);
+            assert_eq!(task_context(this, cx), first_context);
+            assert_eq!(
+                build_context_calls(),
+                1,
+                "Task context should be reused for the same buffer state and selection"
+            );
+
+            editor.update(cx, |this, cx| {
+                this.buffer().update(cx, |buffer, cx| {
+                    buffer.edit([(start + 1..start + 2, "x")], None, cx)
+                })
+            });
+            assert_eq!(
+                task_context(this, cx)
+                    .task_variables
+                    .get(&VariableName::SelectedText),
+                Some("axc"),
+                "Buffer edits should not let a stale task context through"
+            );
+            assert_eq!(build_context_calls(), 2);
+
+            editor.update(cx, |this, cx| {
+                this.change_selections(None, cx, |selections| {
+                    selections.select_ranges([start..start + 2])
+                })
+            });
+            assert_eq!(
+                task_context(this, cx)
+                    .task_variables
+                    .get(&VariableName::SelectedText),
+                Some("ax"),
+                "Selection changes should not let a stale task context through"
+            );
+            assert_eq!(build_context_calls(), 3);
+        });
+
+        cx.executor().advance_clock(Duration::from_millis(500));
+        workspace.update(cx, |this, cx| {
+            task_context(this, cx);
+        });
+        assert_eq!(
+            build_context_calls(),
+            3,
+            "Task context should be reused before it expires"
+        );
+        cx.executor().advance_clock(Duration::from_millis(500));
+        workspace.update(cx, |this, cx| {
+            task_context(this, cx);
+        });
+        assert_eq!(
+            build_context_calls(),
+            4,
+            "Task context should be recomputed after it expires"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_task_context_for_file_outside_of_project(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ "a.rs": "fn a() {}" })).await;
+        fs.insert_tree("/loose", json!({ "script.rs": "fn main() {}" }))
+            .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        workspace
+            .update(cx, |this, cx| {
+                this.open_abs_path(PathBuf::from("/loose/script.rs"), false, cx)
+            })
+            .await
+            .unwrap();
+        workspace.update(cx, |this, cx| {
+            assert_eq!(
+                task_context(this, cx),
+                TaskContext {
+                    cwd: Some("/dir".into()),
+                    task_variables: TaskVariables::from_iter([
+                        (VariableName::File, "/loose/script.rs".into()),
+                        (VariableName::RelativeFile, "/loose/script.rs".into()),
+                        (VariableName::Dirname, "/loose".into()),
+                        (VariableName::Row, "1".into()),
+                        (VariableName::Column, "1".into()),
+                    ])
+                },
+                "File-derived variables should be set, but not the worktree root of a file outside of the project"
+            );
+        });
+    }
+
+    const RUST_TEST_RUNNABLES_QUERY: &str = r#"(
+        (attribute_item (attribute (identifier) @_attribute)
+            (#eq? @_attribute "test"))
+        .
+        (function_item name: (_) @run)
+    ) @rust-test"#;
+
+    fn rust_language() -> Language {
+        Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::language()),
+        )
+    }
+
+    /// Opens an editor for `/dir/a.rs` with `text`, as the active item of a new workspace.
+    async fn editor_for_text<'a>(
+        text: &str,
+        language: Option<Arc<Language>>,
+        cx: &'a mut TestAppContext,
+    ) -> (View<Workspace>, View<Editor>, &'a mut VisualTestContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ "a.rs": text })).await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let editor = open_editor(&workspace, worktree_id, "a.rs", cx).await;
+        if let Some(language) = language {
+            editor.update(cx, |editor, cx| {
+                let buffer = editor.buffer().read(cx).as_singleton().unwrap();
+                buffer.update(cx, |buffer, cx| buffer.set_language(Some(language), cx));
+            });
+            cx.run_until_parked();
+        }
+        workspace.update(cx, |this, cx| {
+            this.add_item_to_center(Box::new(editor.clone()), cx)
+        });
+        (workspace, editor, cx)
+    }
+
+    async fn open_editor(
+        workspace: &View<Workspace>,
+        worktree_id: WorktreeId,
+        path: &str,
+        cx: &mut VisualTestContext,
+    ) -> View<Editor> {
+        let buffer = workspace
+            .update(cx, |this, cx| {
+                this.project()
+                    .update(cx, |this, cx| this.open_buffer((worktree_id, path), cx))
+            })
+            .await
+            .unwrap();
+        let project = workspace.update(cx, |this, _| this.project().clone());
+        cx.new_view(|cx| Editor::for_buffer(buffer, Some(project), cx))
+    }
+
     pub(crate) fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
         cx.update(|cx| {
             let state = AppState::test(cx);
@@ -544,7 +544,7 @@ mod tests {
     use project::{ContextProviderWithTasks, FakeFs, Project};
     use serde_json::json;
     use task::TaskTemplates;
-    use workspace::CloseInactiveTabsAndPanes;
+    use workspace::{item::test::TestItem, CloseInactiveTabsAndPanes};
 
     use crate::{modal::Spawn, tests::init_test};
 
@@ -957,6 +957,58 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_language_tasks_of_recent_editor(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ "b.rs": "// b" })).await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        project.read_with(cx, |project, _| {
+            project.languages().add(Arc::new(
+                Language::new(
+                    LanguageConfig {
+                        name: "Rust".into(),
+                        matcher: LanguageMatcher {
+                            path_suffixes: vec!["rs".to_string()],
+                            ..LanguageMatcher::default()
+                        },
+                        ..LanguageConfig::default()
+                    },
+                    None,
+                )
+                .with_context_provider(Some(Arc::new(
+                    ContextProviderWithTasks::new(TaskTemplates(vec![TaskTemplate {
+                        label: "Rust task from file $ZED_FILE".to_string(),
+                        command: "cargo check".into(),
+                        ..TaskTemplate::default()
+                    }])),
+                ))),
+            ));
+        });
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        let _rs_file = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(PathBuf::from("/dir/b.rs"), true, cx)
+            })
+            .await
+            .unwrap();
+        // Stands in for a terminal or any other non-editor item.
+        let non_editor_item = cx.new_view(TestItem::new);
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item_to_active_pane(Box::new(non_editor_item), None, cx)
+        });
+        cx.executor().run_until_parked();
+
+        let tasks_picker = open_spawn_tasks(&workspace, cx);
+        assert_eq!(
+            task_names(&tasks_picker, cx),
+            vec!["Rust task from file /dir/b.rs"],
+            "With a non-editor item active, the most recently activated editor's language tasks should be listed"
+        );
+    }
+
     fn emulate_task_schedule(
         tasks_picker: View<Picker<TasksModalDelegate>>,
         project: &Model<Project>,